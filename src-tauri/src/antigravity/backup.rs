@@ -0,0 +1,71 @@
+//! Antigravity 账号备份 / 恢复
+//! 把当前账号的 state.vscdb / state.vscdb.backup 打包成一份版本化归档（[`backup_archive`]），
+//! 或者反过来把归档解压回 Antigravity 的数据目录；每次备份/恢复都会登记进
+//! [`crate::backup_retention`] 的索引，供保留策略和垃圾回收使用。
+
+use std::path::PathBuf;
+
+use crate::antigravity::repair;
+use crate::backup_archive;
+use crate::backup_retention;
+use crate::platform;
+
+fn new_backup_id(prefix: &str) -> String {
+    format!("{}-{}", prefix, chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"))
+}
+
+/// 把当前登录账号的状态数据库打包成一份版本化归档，并登记进备份索引
+#[tauri::command]
+pub async fn backup_antigravity_current_account(
+    account_email: String,
+    backup_dir: String,
+) -> Result<String, String> {
+    let app_data = platform::get_antigravity_db_path()
+        .ok_or_else(|| "未找到 Antigravity 安装位置".to_string())?;
+    if !app_data.exists() {
+        return Err(format!(
+            "Antigravity 状态数据库不存在: {}",
+            app_data.display()
+        ));
+    }
+
+    let mut source_files = vec![app_data.clone()];
+    let backup_db = app_data.with_extension("vscdb.backup");
+    if backup_db.exists() {
+        source_files.push(backup_db);
+    }
+
+    let id = new_backup_id("antigravity");
+    let archive_path = PathBuf::from(backup_dir).join(format!("{}.tar.gz", id));
+
+    backup_retention::create_and_index_backup(id, &archive_path, &source_files, &account_email)?;
+
+    Ok(format!("账号备份已创建: {}", archive_path.display()))
+}
+
+/// 从版本化归档恢复 Antigravity 账号数据；恢复完成后顺带跑一次完整性检查，
+/// 让归档本身或恢复过程引入的损坏能尽早被发现、尝试自动修复。
+#[tauri::command]
+pub async fn restore_antigravity_account(archive_path: String) -> Result<String, String> {
+    let archive_path = PathBuf::from(archive_path);
+    let app_data = platform::get_antigravity_db_path()
+        .ok_or_else(|| "未找到 Antigravity 安装位置".to_string())?;
+    let dest_dir = app_data
+        .parent()
+        .ok_or_else(|| "无法确定 Antigravity 数据目录".to_string())?
+        .to_path_buf();
+
+    let metadata = backup_archive::restore_archive(&archive_path, &dest_dir)?;
+    backup_retention::mark_restored(&archive_path)?;
+
+    // 恢复路径最容易暴露出损坏的归档/数据库，顺带跑一次完整性检查并尝试修复
+    match repair::repair_antigravity_database().await {
+        Ok(report) if report.main_db.corrupted => {
+            tracing::warn!(target: "cleanup::repair", detail = %report.main_db.detail, "恢复后检测到数据库损坏，已尝试自动修复");
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(target: "cleanup::repair", error = %e, "恢复后完整性检查失败"),
+    }
+
+    Ok(format!("账号已从备份恢复 (schema v{})", metadata.schema_version))
+}
@@ -6,6 +6,7 @@ use serde_json::Value;
 use std::path::Path;
 
 // 导入 platform_utils 模块
+use crate::antigravity::wal::UndoJournal;
 use crate::constants::database;
 use crate::platform;
 
@@ -13,7 +14,13 @@ use crate::platform;
 const DELETE_KEYS: &[&str] = database::DELETE_KEYS;
 
 /// 智能更新 Marker：彻底移除指定的 Key（而非设为0）
-fn remove_keys_from_marker(conn: &Connection, keys_to_remove: &[&str]) -> Result<(), String> {
+/// 修改前会先把原始 Marker JSON 写入撤销日志，确保崩溃时可以还原
+fn remove_keys_from_marker(
+    conn: &Connection,
+    keys_to_remove: &[&str],
+    journal: &mut UndoJournal,
+    db_path: &Path,
+) -> Result<(), String> {
     tracing::debug!(target: "cleanup::marker", "正在修正校验标记 (Marker)");
 
     let current_marker_json: Option<String> = conn
@@ -28,8 +35,8 @@ fn remove_keys_from_marker(conn: &Connection, keys_to_remove: &[&str]) -> Result
         .optional()
         .map_err(|e| format!("读取 Marker 失败: {}", e))?;
 
-    let mut marker_obj: serde_json::Map<String, Value> = match current_marker_json {
-        Some(s) => serde_json::from_str(&s).unwrap_or_default(),
+    let mut marker_obj: serde_json::Map<String, Value> = match &current_marker_json {
+        Some(s) => serde_json::from_str(s).unwrap_or_default(),
         None => return Ok(()), // 没有 Marker 就不需要处理
     };
 
@@ -42,6 +49,9 @@ fn remove_keys_from_marker(conn: &Connection, keys_to_remove: &[&str]) -> Result
     }
 
     if changed {
+        // 先落盘原始 Marker，再执行真正的修改
+        journal.record_marker(db_path, current_marker_json)?;
+
         let new_marker_str =
             serde_json::to_string(&marker_obj).map_err(|e| format!("序列化失败: {}", e))?;
 
@@ -61,26 +71,42 @@ fn remove_keys_from_marker(conn: &Connection, keys_to_remove: &[&str]) -> Result
     Ok(())
 }
 
+/// 清理单个数据库：先把每个受影响 key 的旧值写入撤销日志并 fsync，
+/// 再在单个事务内执行所有 DELETE/Marker 修改，最后追加提交记录。
+/// 这样即使进程在事务提交前被杀死，下次启动时也能通过 `wal::recover_pending_operations` 回滚。
 fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, String> {
     tracing::info!(target: "cleanup::database", db_name = %db_name, "开始清理数据库");
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    let mut conn = Connection::open(db_path).map_err(|e| e.to_string())?;
 
-    let mut count = 0;
-    // 1. 物理删除数据行
+    let mut journal = UndoJournal::begin(&format!("clear_database:{}", db_name))?;
     for key in DELETE_KEYS {
-        let rows = conn
-            .execute("DELETE FROM ItemTable WHERE key = ?", [key])
-            .unwrap_or(0);
-        if rows > 0 {
-            tracing::debug!(target: "cleanup::database", key = %key, "已删除字段");
-            count += 1;
+        let old_value = crate::antigravity::wal::read_current_value(&conn, key)?;
+        if old_value.is_some() {
+            journal.record_key(db_path, key, old_value)?;
         }
     }
 
-    // 2. 同步修改 Marker 清单
-    if let Err(e) = remove_keys_from_marker(&conn, DELETE_KEYS) {
-        tracing::warn!(target: "cleanup::marker", error = %e, "Marker 更新警告");
+    let mut count = 0;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    {
+        // 1. 物理删除数据行
+        for key in DELETE_KEYS {
+            let rows = tx
+                .execute("DELETE FROM ItemTable WHERE key = ?", [key])
+                .unwrap_or(0);
+            if rows > 0 {
+                tracing::debug!(target: "cleanup::database", key = %key, "已删除字段");
+                count += 1;
+            }
+        }
+
+        // 2. 同步修改 Marker 清单
+        if let Err(e) = remove_keys_from_marker(&tx, DELETE_KEYS, &mut journal, db_path) {
+            tracing::warn!(target: "cleanup::marker", error = %e, "Marker 更新警告");
+        }
     }
+    tx.commit().map_err(|e| format!("提交清理事务失败: {}", e))?;
+    journal.commit()?;
 
     Ok(count)
 }
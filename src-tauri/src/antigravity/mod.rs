@@ -0,0 +1,6 @@
+//! Antigravity 账号数据相关子模块：清理、崩溃恢复日志、数据库完整性修复
+
+pub mod cleanup;
+pub mod wal;
+pub mod repair;
+pub mod backup;
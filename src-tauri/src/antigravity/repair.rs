@@ -0,0 +1,265 @@
+//! Antigravity 状态数据库的完整性校验与修复
+//! 借鉴"手动修复模式"的思路：先跑 SQLite 自带的完整性检查，
+//! 确认 `ItemTable` 存在且 Marker JSON 能正常解析；发现损坏时，
+//! 把能读到的 (key, value) 逐行搬到一个新数据库文件，校验通过后再替换回去。
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::database;
+use crate::platform;
+
+/// 单个数据库文件的检查结果，汇总后返回给前端展示
+#[derive(Debug, Serialize)]
+pub struct DatabaseCheckReport {
+    pub db_name: String,
+    pub checks_run: Vec<String>,
+    pub corrupted: bool,
+    pub rebuilt: bool,
+    pub rows_recovered: usize,
+    pub detail: String,
+}
+
+/// 整体修复报告：主库 + 备份库各一份检查结果
+#[derive(Debug, Serialize)]
+pub struct RepairReport {
+    pub main_db: DatabaseCheckReport,
+    pub backup_db: Option<DatabaseCheckReport>,
+}
+
+/// `PRAGMA quick_check` / `integrity_check` 是否都返回 "ok"
+fn run_integrity_pragmas(conn: &Connection) -> Result<bool, String> {
+    let quick: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| format!("quick_check 失败: {}", e))?;
+    if quick != "ok" {
+        return Ok(false);
+    }
+
+    let full: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("integrity_check 失败: {}", e))?;
+    Ok(full == "ok")
+}
+
+/// 确认 ItemTable 存在
+fn has_item_table(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'ItemTable'",
+        [],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+/// Marker 是否存在且能正常解析为 JSON（没有 Marker 也算正常，代表尚未登录）
+fn marker_parses(conn: &Connection) -> bool {
+    let marker: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT value FROM ItemTable WHERE key = '{}'",
+                database::TARGET_STORAGE_MARKER
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+
+    match marker {
+        Some(s) => serde_json::from_str::<serde_json::Value>(&s).is_ok(),
+        None => true,
+    }
+}
+
+/// 把能读到的 (key, value) 行逐行拷贝到一个全新的数据库文件中，
+/// 保留设备指纹 key（`google.antigravity`），与 `clear_all_antigravity_data` 的约定一致。
+fn rebuild_database(db_path: &Path) -> Result<(PathBuf, usize), String> {
+    let rebuilt_path = db_path.with_extension("vscdb.rebuilt");
+    if rebuilt_path.exists() {
+        fs::remove_file(&rebuilt_path).map_err(|e| format!("清理旧的重建文件失败: {}", e))?;
+    }
+
+    let src = Connection::open(db_path).map_err(|e| format!("打开原数据库失败: {}", e))?;
+    let dst = Connection::open(&rebuilt_path).map_err(|e| format!("创建重建数据库失败: {}", e))?;
+    dst.execute(
+        "CREATE TABLE IF NOT EXISTS ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+        [],
+    )
+    .map_err(|e| format!("初始化重建数据库失败: {}", e))?;
+
+    let mut recovered = 0usize;
+    let mut stmt = src
+        .prepare("SELECT key, value FROM ItemTable")
+        .map_err(|e| format!("准备逐行恢复查询失败: {}", e))?;
+    let mut rows = stmt
+        .query([])
+        .map_err(|e| format!("执行逐行恢复查询失败: {}", e))?;
+
+    // `.recover` 风格：逐行读取，单行损坏就跳过而不是整体失败
+    loop {
+        let row = match rows.next() {
+            Ok(Some(r)) => r,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!(target: "cleanup::repair", error = %e, "跳过一行无法读取的数据");
+                continue;
+            }
+        };
+
+        let key: String = match row.get(0) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        // value 是 BLOB，按 String 读取会让任何非 UTF-8 行直接丢失，必须原样按字节读取
+        let value: Vec<u8> = match row.get(1) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        if dst
+            .execute(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?1, ?2)",
+                rusqlite::params![key, value],
+            )
+            .is_ok()
+        {
+            recovered += 1;
+        }
+    }
+
+    Ok((rebuilt_path, recovered))
+}
+
+/// 对单个数据库文件执行检查，必要时重建并原地替换
+fn check_and_repair(db_path: &Path, db_name: &str) -> DatabaseCheckReport {
+    let mut checks_run = Vec::new();
+
+    if !db_path.exists() {
+        return DatabaseCheckReport {
+            db_name: db_name.to_string(),
+            checks_run,
+            corrupted: false,
+            rebuilt: false,
+            rows_recovered: 0,
+            detail: "数据库文件不存在，跳过".to_string(),
+        };
+    }
+
+    let conn = match Connection::open(db_path) {
+        Ok(c) => c,
+        Err(e) => {
+            return DatabaseCheckReport {
+                db_name: db_name.to_string(),
+                checks_run,
+                corrupted: true,
+                rebuilt: false,
+                rows_recovered: 0,
+                detail: format!("无法打开数据库: {}", e),
+            }
+        }
+    };
+
+    checks_run.push("quick_check".to_string());
+    checks_run.push("integrity_check".to_string());
+    let integrity_ok = run_integrity_pragmas(&conn).unwrap_or(false);
+
+    checks_run.push("item_table_exists".to_string());
+    let table_ok = has_item_table(&conn);
+
+    checks_run.push("marker_parses".to_string());
+    let marker_ok = table_ok && marker_parses(&conn);
+
+    let corrupted = !integrity_ok || !table_ok || !marker_ok;
+    if !corrupted {
+        tracing::info!(target: "cleanup::repair", db_name = %db_name, "数据库校验通过");
+        return DatabaseCheckReport {
+            db_name: db_name.to_string(),
+            checks_run,
+            corrupted: false,
+            rebuilt: false,
+            rows_recovered: 0,
+            detail: "校验通过，无需修复".to_string(),
+        };
+    }
+
+    tracing::warn!(target: "cleanup::repair", db_name = %db_name, "检测到数据库损坏，开始重建");
+    drop(conn);
+
+    match rebuild_database(db_path) {
+        Ok((rebuilt_path, recovered)) => {
+            let backup_of_corrupt = db_path.with_extension("vscdb.corrupt");
+            if let Err(e) = fs::rename(db_path, &backup_of_corrupt) {
+                return DatabaseCheckReport {
+                    db_name: db_name.to_string(),
+                    checks_run,
+                    corrupted: true,
+                    rebuilt: false,
+                    rows_recovered: recovered,
+                    detail: format!("重建成功但无法备份原文件: {}", e),
+                };
+            }
+            if let Err(e) = fs::rename(&rebuilt_path, db_path) {
+                // 换回去失败时，至少把损坏备份恢复原名，避免账号数据彻底丢失
+                let _ = fs::rename(&backup_of_corrupt, db_path);
+                return DatabaseCheckReport {
+                    db_name: db_name.to_string(),
+                    checks_run,
+                    corrupted: true,
+                    rebuilt: false,
+                    rows_recovered: recovered,
+                    detail: format!("重建完成但替换失败: {}", e),
+                };
+            }
+
+            tracing::info!(target: "cleanup::repair", db_name = %db_name, rows_recovered = recovered, "数据库已重建并替换");
+            DatabaseCheckReport {
+                db_name: db_name.to_string(),
+                checks_run,
+                corrupted: true,
+                rebuilt: true,
+                rows_recovered: recovered,
+                detail: format!("检测到损坏，已从 {} 行可读数据重建", recovered),
+            }
+        }
+        Err(e) => DatabaseCheckReport {
+            db_name: db_name.to_string(),
+            checks_run,
+            corrupted: true,
+            rebuilt: false,
+            rows_recovered: 0,
+            detail: format!("重建失败: {}", e),
+        },
+    }
+}
+
+/// 校验并在必要时修复 state.vscdb / state.vscdb.backup。
+/// 既暴露为 `repair_antigravity_database` 命令供前端手动触发，也在启动时和
+/// 账号恢复完成后自动跑一遍，让损坏的数据库能够被尽早发现和修复。
+#[tauri::command]
+pub async fn repair_antigravity_database() -> Result<RepairReport, String> {
+    tracing::info!(target: "cleanup::repair", "开始数据库完整性检查");
+
+    let app_data = match platform::get_antigravity_db_path() {
+        Some(p) => p,
+        None => {
+            let possible_paths = platform::get_all_antigravity_db_paths();
+            possible_paths
+                .into_iter()
+                .next()
+                .ok_or_else(|| "未找到 Antigravity 安装位置".to_string())?
+        }
+    };
+
+    let main_db = check_and_repair(&app_data, "state.vscdb");
+
+    let backup_path = app_data.with_extension("vscdb.backup");
+    let backup_db = if backup_path.exists() {
+        Some(check_and_repair(&backup_path, "state.vscdb.backup"))
+    } else {
+        None
+    };
+
+    Ok(RepairReport { main_db, backup_db })
+}
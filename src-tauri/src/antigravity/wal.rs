@@ -0,0 +1,226 @@
+//! 清理操作的预写式撤销日志（WAL）
+//! 在对 state.vscdb / state.vscdb.backup 做破坏性修改之前，先把受影响 key 的旧值
+//! 和原始 Marker JSON 落盘并 fsync，再在单个事务内执行真正的修改；这样即使中途被杀死
+//! （例如紧跟在 `kill_antigravity` 之后），也能在下次启动时回滚到修改前的状态。
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::constants::database;
+use crate::path_utils;
+
+const JOURNAL_DIR: &str = "recovery";
+const JOURNAL_FILE: &str = "cleanup.wal";
+
+/// 进程内自增计数器，保证同一次运行中两次 `begin` 不会复用同一个 op_id——
+/// 否则同一个 DB 在一次会话内被清理两次时，第二次的 COMMIT 会让
+/// `recover_pending_operations` 把第一次早已提交的记录也当作"已提交"放过，
+/// 真正未提交的记录反而被跳过，崩溃恢复形同虚设。
+static NEXT_OP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 日志中的一条记录。日志是按追加顺序写入的 JSON Lines 文件，
+/// `seq` 单调递增，`op_id` 把同一次清理操作的所有记录串起来。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum JournalRecord {
+    /// 修改某个 key 之前记录的旧值（key 原本不存在时为 `None`）。
+    /// `value` 列是 BLOB，按字节存取，和 `repair.rs` 重建数据库时的读法保持一致，
+    /// 避免非 UTF-8 的旧值在回滚前就已经丢失。
+    Key {
+        seq: u64,
+        op_id: String,
+        db_path: PathBuf,
+        key: String,
+        old_value: Option<Vec<u8>>,
+    },
+    /// 修改 Marker 之前记录的原始 Marker JSON
+    Marker {
+        seq: u64,
+        op_id: String,
+        db_path: PathBuf,
+        old_marker: Option<String>,
+    },
+    /// 本次操作的所有修改都已经成功提交
+    Commit { op_id: String },
+}
+
+fn journal_path() -> Result<PathBuf, String> {
+    let dir = path_utils::config_dir().join(JOURNAL_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建恢复目录失败: {}", e))?;
+    Ok(dir.join(JOURNAL_FILE))
+}
+
+/// 一次清理操作的撤销日志句柄：先调用 `record_*` 写入旧值，再执行真正的
+/// SQL 修改，最后调用 `commit` 追加提交记录。
+pub struct UndoJournal {
+    op_id: String,
+    file: File,
+    seq: u64,
+}
+
+impl UndoJournal {
+    /// 打开（或创建）journal 文件并开始一次新的操作。
+    /// `op_id` 会附加一个进程内单调递增的序号，确保同一个 `op_id` 前缀
+    /// （例如 `clear_database:state.vscdb`）在一次会话里被多次使用时互不冲突。
+    pub fn begin(op_id: &str) -> Result<Self, String> {
+        let path = journal_path()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("打开撤销日志失败: {}", e))?;
+
+        let op_id = format!("{}#{}", op_id, NEXT_OP_SEQ.fetch_add(1, Ordering::Relaxed));
+        tracing::debug!(target: "cleanup::wal", op_id = %op_id, "开始记录撤销日志");
+        Ok(Self {
+            op_id,
+            file,
+            seq: 0,
+        })
+    }
+
+    fn append(&mut self, record: &JournalRecord) -> Result<(), String> {
+        let mut line = serde_json::to_string(record).map_err(|e| format!("序列化撤销记录失败: {}", e))?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("写入撤销日志失败: {}", e))?;
+        self.file.sync_all().map_err(|e| format!("fsync 撤销日志失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 记录某个 key 在修改前的值（按原始字节存储，value 列是 BLOB）
+    pub fn record_key(&mut self, db_path: &Path, key: &str, old_value: Option<Vec<u8>>) -> Result<(), String> {
+        self.seq += 1;
+        self.append(&JournalRecord::Key {
+            seq: self.seq,
+            op_id: self.op_id.clone(),
+            db_path: db_path.to_path_buf(),
+            key: key.to_string(),
+            old_value,
+        })
+    }
+
+    /// 记录 Marker 在修改前的原始 JSON
+    pub fn record_marker(&mut self, db_path: &Path, old_marker: Option<String>) -> Result<(), String> {
+        self.seq += 1;
+        self.append(&JournalRecord::Marker {
+            seq: self.seq,
+            op_id: self.op_id.clone(),
+            db_path: db_path.to_path_buf(),
+            old_marker,
+        })
+    }
+
+    /// 标记本次操作的所有修改都已经成功写入数据库
+    pub fn commit(mut self) -> Result<(), String> {
+        self.append(&JournalRecord::Commit {
+            op_id: self.op_id.clone(),
+        })?;
+        tracing::debug!(target: "cleanup::wal", op_id = %self.op_id, "撤销日志已提交");
+        Ok(())
+    }
+}
+
+/// 启动时调用：扫描撤销日志，回滚所有没有匹配 COMMIT 记录的操作，然后清空日志文件。
+/// 同时也作为 `recover_pending_operations` 命令暴露给前端，用于手动触发一次恢复检查。
+#[tauri::command]
+pub fn recover_pending_operations() -> Result<String, String> {
+    let path = journal_path()?;
+    if !path.exists() {
+        return Ok("无待恢复的清理操作".to_string());
+    }
+
+    let file = File::open(&path).map_err(|e| format!("打开撤销日志失败: {}", e))?;
+    let reader = BufReader::new(file);
+
+    let mut records: Vec<JournalRecord> = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("读取撤销日志失败: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JournalRecord>(&line) {
+            Ok(r) => records.push(r),
+            Err(e) => {
+                tracing::warn!(target: "cleanup::wal", error = %e, "忽略无法解析的撤销记录");
+            }
+        }
+    }
+
+    let committed: std::collections::HashSet<String> = records
+        .iter()
+        .filter_map(|r| match r {
+            JournalRecord::Commit { op_id } => Some(op_id.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut rolled_back = 0usize;
+    for record in &records {
+        let op_id = match record {
+            JournalRecord::Key { op_id, .. } | JournalRecord::Marker { op_id, .. } => op_id,
+            JournalRecord::Commit { .. } => continue,
+        };
+        if committed.contains(op_id) {
+            continue;
+        }
+
+        match record {
+            JournalRecord::Key { db_path, key, old_value, .. } => {
+                if restore_key(db_path, key, old_value.as_deref()).is_ok() {
+                    rolled_back += 1;
+                }
+            }
+            JournalRecord::Marker { db_path, old_marker, .. } => {
+                if restore_marker(db_path, old_marker.as_deref()).is_ok() {
+                    rolled_back += 1;
+                }
+            }
+            JournalRecord::Commit { .. } => {}
+        }
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("清空撤销日志失败: {}", e))?;
+
+    let msg = format!("恢复了 {} 条未提交的清理操作记录", rolled_back);
+    tracing::info!(target: "cleanup::wal", rolled_back, "{}", msg);
+    Ok(msg)
+}
+
+fn restore_key(db_path: &Path, key: &str, old_value: Option<&[u8]>) -> Result<(), String> {
+    if !db_path.exists() {
+        return Err(format!("数据库不存在，无法恢复: {}", db_path.display()));
+    }
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    match old_value {
+        Some(v) => conn
+            .execute(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?1, ?2)",
+                rusqlite::params![key, v],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        None => conn
+            .execute("DELETE FROM ItemTable WHERE key = ?", [key])
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+fn restore_marker(db_path: &Path, old_marker: Option<&str>) -> Result<(), String> {
+    restore_key(db_path, database::TARGET_STORAGE_MARKER, old_marker.map(|s| s.as_bytes()))
+}
+
+/// 辅助函数：读取某个 key 修改前的值，供调用方在执行真正的修改前先写入日志。
+/// `value` 列是 BLOB，按 `Vec<u8>` 原样读取——按 `String` 读会让任何非 UTF-8 的旧值
+/// 直接触发 `query_row` 报错，在还没删除一行之前就整体中止清理。
+pub fn read_current_value(conn: &Connection, key: &str) -> Result<Option<Vec<u8>>, String> {
+    conn.query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("读取 key 旧值失败: {}", e))
+}
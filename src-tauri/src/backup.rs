@@ -0,0 +1,47 @@
+//! 通用 profile 备份 / 恢复命令
+//! 基于版本化归档格式（[`crate::backup_archive`]），每次备份/恢复都登记进
+//! [`crate::backup_retention`] 的索引，供保留策略和垃圾回收使用。
+
+use std::path::PathBuf;
+
+use crate::backup_archive;
+use crate::backup_retention;
+
+fn new_backup_id() -> String {
+    format!("profile-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"))
+}
+
+/// 把一组 profile 文件打包成版本化归档，并登记进备份索引
+#[tauri::command]
+pub async fn backup_profile(
+    account_email: String,
+    source_files: Vec<String>,
+    backup_dir: String,
+) -> Result<String, String> {
+    let source_files: Vec<PathBuf> = source_files.into_iter().map(PathBuf::from).collect();
+    let id = new_backup_id();
+    let archive_path = PathBuf::from(backup_dir).join(format!("{}.tar.gz", id));
+
+    backup_retention::create_and_index_backup(id, &archive_path, &source_files, &account_email)?;
+
+    Ok(format!("备份已创建: {}", archive_path.display()))
+}
+
+/// 从版本化归档恢复 profile 文件到 `dest_dir`
+#[tauri::command]
+pub async fn restore_profile(archive_path: String, dest_dir: String) -> Result<String, String> {
+    restore_backup_files(archive_path, dest_dir).await
+}
+
+/// 从版本化归档恢复文件；恢复成功后更新索引里的"最近恢复时间"，
+/// 供保留策略按"最近最少被恢复"优先淘汰。
+#[tauri::command]
+pub async fn restore_backup_files(archive_path: String, dest_dir: String) -> Result<String, String> {
+    let archive_path = PathBuf::from(archive_path);
+    let dest_dir = PathBuf::from(dest_dir);
+
+    let metadata = backup_archive::restore_archive(&archive_path, &dest_dir)?;
+    backup_retention::mark_restored(&archive_path)?;
+
+    Ok(format!("恢复完成 (schema v{})", metadata.schema_version))
+}
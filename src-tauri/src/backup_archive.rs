@@ -0,0 +1,221 @@
+//! 自描述、带版本号的压缩备份归档格式
+//!
+//! 过去 `backup_profile` / `restore_profile` 一系列命令只是把 profile 目录下的文件原样
+//! 拷贝到备份目录，一旦磁盘布局或 `DELETE_KEYS` 集合发生变化，旧备份就无法正确恢复。
+//! 这里把备份统一打包成单个 `.tar.gz` 归档：内含原始文件 + 一个 `metadata.json`
+//! （记录 schema 版本、app 版本、创建时间、平台信息、脱敏后的账号邮箱）。
+//! 恢复时先读 `metadata.json`，按 `schema_version` 派发到对应的版本化加载器，
+//! 逐级迁移到当前 schema，再把文件写回目标目录；比当前运行版本更新的归档直接拒绝恢复。
+
+use flate2::write::GzEncoder;
+use flate2::read::GzDecoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::utils::log_sanitizer::LogSanitizer;
+
+/// 归档当前使用的 schema 版本，新增不兼容改动时递增，并补一个 `load_vN`
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+const METADATA_FILE: &str = "metadata.json";
+const FILES_DIR: &str = "files";
+
+/// 归档内 `metadata.json` 的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupMetadata {
+    pub schema_version: u32,
+    pub app_version: String,
+    /// RFC3339 格式的 UTC 时间戳
+    pub created_at: String,
+    pub platform: String,
+    pub arch: String,
+    /// 经过 `LogSanitizer` 脱敏后的账号邮箱，归档内不保留明文
+    pub account_email: String,
+}
+
+impl BackupMetadata {
+    fn new(account_email: &str, created_at: &str) -> Self {
+        let sanitizer = LogSanitizer::new();
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: created_at.to_string(),
+            platform: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            account_email: sanitizer.sanitize_email(account_email),
+        }
+    }
+}
+
+/// 把一组文件打包成单个自描述的 `.tar.gz` 归档
+pub fn create_archive(
+    archive_path: &Path,
+    source_files: &[PathBuf],
+    account_email: &str,
+    created_at: &str,
+) -> Result<(), String> {
+    tracing::info!(target: "backup::archive", archive = %archive_path.display(), "开始创建备份归档");
+
+    let file = fs::File::create(archive_path).map_err(|e| format!("创建归档文件失败: {}", e))?;
+    let encoder = GzEncoder::new(file, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    let metadata = BackupMetadata::new(account_email, created_at);
+    let metadata_json =
+        serde_json::to_vec_pretty(&metadata).map_err(|e| format!("序列化归档元数据失败: {}", e))?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(metadata_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, METADATA_FILE, metadata_json.as_slice())
+        .map_err(|e| format!("写入归档元数据失败: {}", e))?;
+
+    for src in source_files {
+        let file_name = src
+            .file_name()
+            .ok_or_else(|| format!("无效的备份源文件路径: {}", src.display()))?;
+        let entry_path = Path::new(FILES_DIR).join(file_name);
+        builder
+            .append_path_with_name(src, &entry_path)
+            .map_err(|e| format!("写入 {} 失败: {}", src.display(), e))?;
+    }
+
+    builder.finish().map_err(|e| format!("完成归档失败: {}", e))?;
+    tracing::info!(target: "backup::archive", archive = %archive_path.display(), files = source_files.len(), "备份归档创建完成");
+    Ok(())
+}
+
+/// 只读取归档内的 `metadata.json`，不做完整解压
+pub fn read_metadata(archive_path: &Path) -> Result<BackupMetadata, String> {
+    let file = fs::File::open(archive_path).map_err(|e| format!("打开归档失败: {}", e))?;
+    let decoder = GzDecoder::new(file);
+    let mut archive = tar::Archive::new(decoder);
+
+    let entries = archive.entries().map_err(|e| format!("读取归档条目失败: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("读取归档条目失败: {}", e))?;
+        let path = entry.path().map_err(|e| format!("读取归档条目路径失败: {}", e))?;
+        if path == Path::new(METADATA_FILE) {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .map_err(|e| format!("读取归档元数据失败: {}", e))?;
+            return serde_json::from_str(&content).map_err(|e| format!("解析归档元数据失败: {}", e));
+        }
+    }
+
+    Err("归档中缺少 metadata.json，可能不是本工具创建的备份".to_string())
+}
+
+/// 把归档内 `files/` 目录下的文件原样解压到 `dest_dir`。这是归档的物理格式，与逻辑 schema
+/// 版本无关——不管 `metadata.schema_version` 是多少，tar 包里的文件都先按这一步落到磁盘，
+/// 再由下面的迁移链就地把它们从归档版本迁移到当前版本。
+fn extract_raw(archive_path: &Path, dest_dir: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path).map_err(|e| format!("打开归档失败: {}", e))?;
+    let decoder = GzDecoder::new(file);
+    let mut archive = tar::Archive::new(decoder);
+
+    fs::create_dir_all(dest_dir).map_err(|e| format!("创建恢复目录失败: {}", e))?;
+
+    let entries = archive.entries().map_err(|e| format!("读取归档条目失败: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("读取归档条目失败: {}", e))?;
+        let path = entry.path().map_err(|e| format!("读取归档条目路径失败: {}", e))?.into_owned();
+
+        let relative = match path.strip_prefix(FILES_DIR) {
+            Ok(r) => r,
+            Err(_) => continue, // 跳过 metadata.json 等非数据文件
+        };
+        let target = dest_dir.join(relative);
+        entry.unpack(&target).map_err(|e| format!("解压 {} 失败: {}", target.display(), e))?;
+    }
+
+    Ok(())
+}
+
+/// 就地迁移函数：把 `dest_dir` 中处于某个 schema 版本的已解压文件，迁移成下一个版本的布局。
+/// `load_v1` 是恒等迁移（当前格式就是 v1 的格式，没有历史包袱需要转换）；新增
+/// 不兼容改动时，在这里补一个 `load_vN`，读取 `dest_dir` 里上一版迁移的产物并就地改写。
+type MigrationStep = fn(&Path) -> Result<(), String>;
+
+fn load_v1(_dest_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// 按 schema 版本排列的迁移链：从归档自带的版本开始，一直迁移到 `CURRENT_SCHEMA_VERSION`，
+/// 每一步都作用在上一步迁移后的 `dest_dir` 上，而不是重新读取归档。
+fn migration_chain(from_version: u32) -> Result<Vec<MigrationStep>, String> {
+    let mut steps: Vec<MigrationStep> = Vec::new();
+    for version in from_version..=CURRENT_SCHEMA_VERSION {
+        let step: MigrationStep = match version {
+            1 => load_v1,
+            v => return Err(format!("不支持的归档 schema 版本: {}", v)),
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// 拒绝比当前应用支持的 schema 版本更新的归档——旧版本应用打开新版本的备份，
+/// 字段含义可能已经变了，贸然恢复比报错更危险。
+fn reject_if_newer_than_current(schema_version: u32) -> Result<(), String> {
+    if schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "备份归档的 schema 版本 ({}) 比当前应用支持的版本 ({}) 更新，请升级应用后再恢复",
+            schema_version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// 从归档恢复到 `dest_dir`：读取元数据、拒绝比当前版本更新的归档，解压后依次跑迁移链，
+/// 把上一步的产物交给下一步，逐级迁移到当前 schema。
+pub fn restore_archive(archive_path: &Path, dest_dir: &Path) -> Result<BackupMetadata, String> {
+    let metadata = read_metadata(archive_path)?;
+    reject_if_newer_than_current(metadata.schema_version)?;
+
+    tracing::info!(
+        target: "backup::archive",
+        schema_version = metadata.schema_version,
+        app_version = %metadata.app_version,
+        "开始恢复备份归档"
+    );
+
+    extract_raw(archive_path, dest_dir)?;
+
+    let steps = migration_chain(metadata.schema_version)?;
+    for step in steps {
+        step(dest_dir)?;
+    }
+
+    tracing::info!(target: "backup::archive", dest = %dest_dir.display(), "备份归档恢复完成");
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migration_chain_from_current_version_is_a_single_identity_step() {
+        let steps = migration_chain(CURRENT_SCHEMA_VERSION).expect("current version must be supported");
+        assert_eq!(steps.len(), 1);
+    }
+
+    #[test]
+    fn reject_if_newer_than_current_allows_current_and_older() {
+        assert!(reject_if_newer_than_current(CURRENT_SCHEMA_VERSION).is_ok());
+        if CURRENT_SCHEMA_VERSION > 0 {
+            assert!(reject_if_newer_than_current(CURRENT_SCHEMA_VERSION - 1).is_ok());
+        }
+    }
+
+    #[test]
+    fn reject_if_newer_than_current_rejects_future_schema() {
+        assert!(reject_if_newer_than_current(CURRENT_SCHEMA_VERSION + 1).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! 备份保留策略与自动垃圾回收
+//!
+//! `clear_all_backups` / `delete_backup` 过去只支持手动、全有或全无的删除方式。
+//! 这里维护一个轻量的 SQLite 索引（`backups/index.db`），记录每个备份的 id、路径、
+//! 所属账号邮箱、字节大小、创建时间和最近一次恢复时间；再配合 `app_settings` 里的
+//! 容量/数量/时效策略，在超出配额时按"最近最少被恢复"优先淘汰。
+//!
+//! 索引的时间戳/大小更新通过一个延迟写缓冲区攒批，等一条命令结束后再一次性落盘，
+//! 避免每次备份/恢复操作都触发一次磁盘 IO（类似缓存追踪器在保存前攒批"最近使用"更新的做法）。
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::backup_archive;
+use crate::path_utils;
+
+const INDEX_DIR: &str = "backups";
+const INDEX_FILE: &str = "index.db";
+
+/// `app_settings` 中的备份保留策略
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// 所有备份总大小上限（字节），0 表示不限制
+    pub max_total_size_bytes: u64,
+    /// 单个账号保留的备份数量上限，0 表示不限制
+    pub max_count_per_account: u32,
+    /// 备份最长保留天数，0 表示不限制
+    pub max_age_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_total_size_bytes: 0,
+            max_count_per_account: 0,
+            max_age_days: 0,
+        }
+    }
+}
+
+/// 索引中的一条备份记录
+#[derive(Debug, Clone)]
+pub struct BackupIndexEntry {
+    pub id: String,
+    pub path: PathBuf,
+    pub account_email: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+    pub last_restored_at: Option<String>,
+}
+
+/// 某次命令内待落盘的增量更新，攒够一批再一次性写入 index.db
+#[derive(Default)]
+struct PendingUpdates {
+    /// id -> 最新的 last_restored_at
+    restored_at: HashMap<String, String>,
+    /// id -> 最新的 size_bytes
+    size_bytes: HashMap<String, u64>,
+}
+
+fn index_path() -> Result<PathBuf, String> {
+    let dir = path_utils::config_dir().join(INDEX_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建备份索引目录失败: {}", e))?;
+    Ok(dir.join(INDEX_FILE))
+}
+
+/// 备份索引，封装对 `backups/index.db` 的读写，并附带一个延迟写缓冲区
+pub struct BackupIndex {
+    conn: Connection,
+    pending: PendingUpdates,
+}
+
+impl BackupIndex {
+    pub fn open() -> Result<Self, String> {
+        let conn = Connection::open(index_path()?).map_err(|e| format!("打开备份索引失败: {}", e))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS backups (
+                id TEXT PRIMARY KEY,
+                path TEXT NOT NULL,
+                account_email TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                last_restored_at TEXT
+            )",
+            [],
+        )
+        .map_err(|e| format!("初始化备份索引表失败: {}", e))?;
+
+        Ok(Self {
+            conn,
+            pending: PendingUpdates::default(),
+        })
+    }
+
+    /// 新建备份时登记一条索引记录（立即写入，属于低频操作）
+    pub fn record_backup(&self, entry: &BackupIndexEntry) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO backups (id, path, account_email, size_bytes, created_at, last_restored_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    entry.id,
+                    entry.path.to_string_lossy(),
+                    entry.account_email,
+                    entry.size_bytes as i64,
+                    entry.created_at,
+                    entry.last_restored_at,
+                ],
+            )
+            .map_err(|e| format!("写入备份索引失败: {}", e))?;
+        Ok(())
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM backups WHERE id = ?", [id])
+            .map_err(|e| format!("删除备份索引失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 标记某个备份刚被恢复过：先攒进缓冲区，等 `flush` 时才真正落盘
+    pub fn touch_restored(&mut self, id: &str, restored_at: &str) {
+        self.pending.restored_at.insert(id.to_string(), restored_at.to_string());
+    }
+
+    /// 更新某个备份的字节大小：同样先攒批
+    pub fn update_size(&mut self, id: &str, size_bytes: u64) {
+        self.pending.size_bytes.insert(id.to_string(), size_bytes);
+    }
+
+    /// 把缓冲区里攒的增量更新在一个事务内一次性写入，命令结束前调用一次即可
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.pending.restored_at.is_empty() && self.pending.size_bytes.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        for (id, restored_at) in self.pending.restored_at.drain() {
+            tx.execute(
+                "UPDATE backups SET last_restored_at = ?1 WHERE id = ?2",
+                rusqlite::params![restored_at, id],
+            )
+            .map_err(|e| format!("刷新最近恢复时间失败: {}", e))?;
+        }
+        for (id, size_bytes) in self.pending.size_bytes.drain() {
+            tx.execute(
+                "UPDATE backups SET size_bytes = ?1 WHERE id = ?2",
+                rusqlite::params![size_bytes as i64, id],
+            )
+            .map_err(|e| format!("刷新备份大小失败: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("提交备份索引更新失败: {}", e))?;
+        Ok(())
+    }
+
+    pub fn all(&self) -> Result<Vec<BackupIndexEntry>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, path, account_email, size_bytes, created_at, last_restored_at FROM backups")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(BackupIndexEntry {
+                    id: row.get(0)?,
+                    path: PathBuf::from(row.get::<_, String>(1)?),
+                    account_email: row.get(2)?,
+                    size_bytes: row.get::<_, i64>(3)? as u64,
+                    created_at: row.get(4)?,
+                    last_restored_at: row.get(5)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn total_size(&self) -> Result<u64, String> {
+        Ok(self.all()?.iter().map(|e| e.size_bytes).sum())
+    }
+}
+
+/// 打包一份版本化归档并登记进备份索引，随后顺带检查一次配额。
+/// `backup_profile` 和 `backup_antigravity_current_account` 的备份流程完全一致，
+/// 只有归档内容和 id 前缀不同，共用这一个函数避免两处重复维护同一套"打包 -> 登记 -> 回收"逻辑。
+pub fn create_and_index_backup(
+    id: String,
+    archive_path: &Path,
+    source_files: &[PathBuf],
+    account_email: &str,
+) -> Result<(), String> {
+    let created_at = chrono::Utc::now().to_rfc3339();
+    backup_archive::create_archive(archive_path, source_files, account_email, &created_at)?;
+
+    let size_bytes = std::fs::metadata(archive_path).map(|m| m.len()).unwrap_or(0);
+    let index = BackupIndex::open()?;
+    index.record_backup(&BackupIndexEntry {
+        id,
+        path: archive_path.to_path_buf(),
+        account_email: account_email.to_string(),
+        size_bytes,
+        created_at: created_at.clone(),
+        last_restored_at: None,
+    })?;
+
+    let policy = crate::app_settings::get_retention_policy();
+    maybe_auto_gc(&policy, &created_at);
+    Ok(())
+}
+
+/// 恢复完成后调用：在索引里按归档路径找到对应记录，更新"最近恢复时间"。
+/// `restore_backup_files` 和 `restore_antigravity_account` 共用这一处收尾逻辑。
+pub fn mark_restored(archive_path: &Path) -> Result<(), String> {
+    let mut index = BackupIndex::open()?;
+    if let Some(id) = index
+        .all()?
+        .into_iter()
+        .find(|e| e.path == archive_path)
+        .map(|e| e.id)
+    {
+        index.touch_restored(&id, &chrono::Utc::now().to_rfc3339());
+        index.flush()?;
+    }
+    Ok(())
+}
+
+/// 淘汰名单：哪些备份会被回收，以及触发回收的原因
+pub struct GcPlan {
+    pub to_evict: Vec<BackupIndexEntry>,
+    pub reason: String,
+}
+
+/// 按策略计算需要淘汰的备份：先按年龄淘汰过期的，再按"每账号数量上限"淘汰多余的，
+/// 最后按总大小上限、以最近最少被恢复优先淘汰，直到回到配额内。
+pub fn plan_eviction(entries: &[BackupIndexEntry], policy: &RetentionPolicy, now: &str) -> GcPlan {
+    let mut remaining: Vec<&BackupIndexEntry> = entries.iter().collect();
+    let mut to_evict: Vec<BackupIndexEntry> = Vec::new();
+    let mut reasons: Vec<String> = Vec::new();
+
+    if policy.max_age_days > 0 {
+        let (expired, fresh): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|e| is_older_than(&e.created_at, now, policy.max_age_days));
+        if !expired.is_empty() {
+            reasons.push(format!("{} 个备份超过 {} 天", expired.len(), policy.max_age_days));
+        }
+        to_evict.extend(expired.into_iter().cloned());
+        remaining = fresh;
+    }
+
+    if policy.max_count_per_account > 0 {
+        let mut by_account: HashMap<String, Vec<&BackupIndexEntry>> = HashMap::new();
+        for e in &remaining {
+            by_account.entry(e.account_email.clone()).or_default().push(e);
+        }
+        let mut keep: Vec<&BackupIndexEntry> = Vec::new();
+        for (_, mut group) in by_account {
+            group.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            if group.len() as u32 > policy.max_count_per_account {
+                let overflow = group.split_off(policy.max_count_per_account as usize);
+                reasons.push(format!("{} 个备份超出单账号数量上限", overflow.len()));
+                to_evict.extend(overflow.into_iter().cloned());
+            }
+            keep.extend(group);
+        }
+        remaining = keep;
+    }
+
+    if policy.max_total_size_bytes > 0 {
+        let mut sorted = remaining;
+        // 最近最少被恢复（从未恢复过的视为最久远）优先淘汰
+        sorted.sort_by(|a, b| last_restored_key(a).cmp(&last_restored_key(b)));
+
+        let mut total: u64 = sorted.iter().map(|e| e.size_bytes).sum();
+        let mut idx = 0;
+        while total > policy.max_total_size_bytes && idx < sorted.len() {
+            total = total.saturating_sub(sorted[idx].size_bytes);
+            to_evict.push(sorted[idx].clone());
+            idx += 1;
+        }
+        if idx > 0 {
+            reasons.push(format!("{} 个备份因总大小超出配额被淘汰", idx));
+        }
+    }
+
+    GcPlan {
+        to_evict,
+        reason: reasons.join("; "),
+    }
+}
+
+fn last_restored_key(e: &BackupIndexEntry) -> String {
+    e.last_restored_at.clone().unwrap_or_else(|| e.created_at.clone())
+}
+
+/// 粗略的"是否超过 N 天"判断：两个时间戳都是 RFC3339 格式字符串，按字典序比较日期部分即可
+fn is_older_than(created_at: &str, now: &str, max_age_days: u32) -> bool {
+    let parse_date = |s: &str| -> Option<chrono::NaiveDate> {
+        chrono::DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.date_naive())
+    };
+    match (parse_date(created_at), parse_date(now)) {
+        (Some(created), Some(now)) => (now - created).num_days() > max_age_days as i64,
+        _ => false,
+    }
+}
+
+/// 执行垃圾回收：淘汰超出配额的备份，物理删除文件并从索引中移除，
+/// 通过 `cleanup::` tracing 目标记录每一次淘汰。
+pub fn garbage_collect(policy: &RetentionPolicy, now: &str) -> Result<String, String> {
+    let index = BackupIndex::open()?;
+    let entries = index.all()?;
+    let plan = plan_eviction(&entries, policy, now);
+
+    if plan.to_evict.is_empty() {
+        tracing::debug!(target: "cleanup::retention", "备份用量在配额内，无需回收");
+        return Ok("未超出配额，无需回收".to_string());
+    }
+
+    tracing::info!(target: "cleanup::retention", count = plan.to_evict.len(), reason = %plan.reason, "开始回收备份");
+
+    let mut evicted = 0;
+    for entry in &plan.to_evict {
+        if let Err(e) = std::fs::remove_file(&entry.path) {
+            tracing::warn!(target: "cleanup::retention", id = %entry.id, error = %e, "删除备份文件失败");
+            continue;
+        }
+        if let Err(e) = index.remove(&entry.id) {
+            tracing::warn!(target: "cleanup::retention", id = %entry.id, error = %e, "从索引中移除备份失败");
+            continue;
+        }
+        tracing::info!(target: "cleanup::retention", id = %entry.id, account = %entry.account_email, "备份已回收");
+        evicted += 1;
+    }
+
+    Ok(format!("已回收 {} 个备份（{}）", evicted, plan.reason))
+}
+
+/// 对外暴露的 `garbage_collect_backups` 命令：按前端传入的策略执行一次回收，
+/// 当前时间在服务端计算，不需要前端传入
+#[tauri::command]
+pub async fn garbage_collect_backups(policy: RetentionPolicy) -> Result<String, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    garbage_collect(&policy, &now)
+}
+
+/// 在 `backup_profile` 成功之后调用：如果任意一项配额被突破就顺带触发一次回收，
+/// 不需要用户手动点击"清理"。三项限制（总大小/单账号数量/最长保留天数）地位相同，
+/// 只要全部为 0（不限制）才真正跳过，具体该不该回收交给 `plan_eviction` 去判断。
+pub fn maybe_auto_gc(policy: &RetentionPolicy, now: &str) {
+    if policy.max_total_size_bytes == 0 && policy.max_count_per_account == 0 && policy.max_age_days == 0 {
+        return;
+    }
+
+    let index = match BackupIndex::open() {
+        Ok(i) => i,
+        Err(e) => {
+            tracing::warn!(target: "cleanup::retention", error = %e, "打开备份索引失败，跳过自动回收");
+            return;
+        }
+    };
+
+    let entries = match index.all() {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::warn!(target: "cleanup::retention", error = %e, "读取备份索引失败，跳过自动回收");
+            return;
+        }
+    };
+
+    if plan_eviction(&entries, policy, now).to_evict.is_empty() {
+        return;
+    }
+
+    if let Err(e) = garbage_collect(policy, now) {
+        tracing::warn!(target: "cleanup::retention", error = %e, "自动回收备份失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, account: &str, created_at: &str, size_bytes: u64, last_restored_at: Option<&str>) -> BackupIndexEntry {
+        BackupIndexEntry {
+            id: id.to_string(),
+            path: PathBuf::from(format!("/tmp/{}.tar.gz", id)),
+            account_email: account.to_string(),
+            size_bytes,
+            created_at: created_at.to_string(),
+            last_restored_at: last_restored_at.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn is_older_than_compares_calendar_days() {
+        assert!(is_older_than("2026-01-01T00:00:00Z", "2026-01-10T00:00:00Z", 7));
+        assert!(!is_older_than("2026-01-05T00:00:00Z", "2026-01-10T00:00:00Z", 7));
+    }
+
+    #[test]
+    fn is_older_than_ignores_unparsable_timestamps() {
+        assert!(!is_older_than("not-a-date", "2026-01-10T00:00:00Z", 0));
+    }
+
+    #[test]
+    fn plan_eviction_evicts_expired_backups_by_age() {
+        let entries = vec![
+            entry("old", "a@x.com", "2026-01-01T00:00:00Z", 10, None),
+            entry("new", "a@x.com", "2026-01-09T00:00:00Z", 10, None),
+        ];
+        let policy = RetentionPolicy { max_age_days: 7, ..RetentionPolicy::default() };
+        let plan = plan_eviction(&entries, &policy, "2026-01-10T00:00:00Z");
+        assert_eq!(plan.to_evict.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["old"]);
+    }
+
+    #[test]
+    fn plan_eviction_keeps_only_max_count_per_account_newest_backups() {
+        let entries = vec![
+            entry("b1", "a@x.com", "2026-01-01T00:00:00Z", 10, None),
+            entry("b2", "a@x.com", "2026-01-02T00:00:00Z", 10, None),
+            entry("b3", "a@x.com", "2026-01-03T00:00:00Z", 10, None),
+        ];
+        let policy = RetentionPolicy { max_count_per_account: 2, ..RetentionPolicy::default() };
+        let plan = plan_eviction(&entries, &policy, "2026-01-10T00:00:00Z");
+        assert_eq!(plan.to_evict.len(), 1);
+        assert_eq!(plan.to_evict[0].id, "b1");
+    }
+
+    #[test]
+    fn plan_eviction_evicts_by_size_least_recently_restored_first() {
+        let entries = vec![
+            entry("never-restored", "a@x.com", "2026-01-01T00:00:00Z", 100, None),
+            entry("recently-restored", "a@x.com", "2026-01-01T00:00:00Z", 100, Some("2026-01-09T00:00:00Z")),
+        ];
+        let policy = RetentionPolicy { max_total_size_bytes: 150, ..RetentionPolicy::default() };
+        let plan = plan_eviction(&entries, &policy, "2026-01-10T00:00:00Z");
+        assert_eq!(plan.to_evict.len(), 1);
+        assert_eq!(plan.to_evict[0].id, "never-restored");
+    }
+
+    #[test]
+    fn plan_eviction_is_noop_when_all_limits_are_zero() {
+        let entries = vec![entry("b1", "a@x.com", "2020-01-01T00:00:00Z", 1_000_000, None)];
+        let plan = plan_eviction(&entries, &RetentionPolicy::default(), "2026-01-10T00:00:00Z");
+        assert!(plan.to_evict.is_empty());
+    }
+}
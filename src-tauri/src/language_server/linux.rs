@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+use read_process_memory::{CopyAddress, Pid, ProcessHandle};
+use regex::Regex;
+use std::convert::TryInto;
+use std::fs;
+use std::io::{BufRead, BufReader};
+
+use crate::language_server::utils::{search_bytes_for_token, CHUNK_SIZE, SCAN_AHEAD, MAX_REGION_BYTES};
+
+/// `/proc/<pid>/maps` 里的一行，只保留我们关心的字段
+struct MapRegion {
+    start: usize,
+    end: usize,
+    readable: bool,
+    /// 文件映射且仅可执行（例如共享库的 `.text` 段），这类区域基本不会持有凭据，跳过以减少扫描量
+    exec_only_file_backed: bool,
+}
+
+fn parse_maps_line(line: &str) -> Option<MapRegion> {
+    // 形如: 7f1234000000-7f1234021000 r--p 00000000 08:01 1234  /lib/x86_64-linux-gnu/libc.so.6
+    let mut parts = line.split_whitespace();
+    let range = parts.next()?;
+    let perms = parts.next()?;
+    let _offset = parts.next()?;
+    let _dev = parts.next()?;
+    let _inode = parts.next()?;
+    let path = parts.next();
+
+    let (start_s, end_s) = range.split_once('-')?;
+    let start = usize::from_str_radix(start_s, 16).ok()?;
+    let end = usize::from_str_radix(end_s, 16).ok()?;
+
+    let readable = perms.starts_with('r');
+    let writable = perms.as_bytes().get(1) == Some(&b'w');
+    let executable = perms.as_bytes().get(2) == Some(&b'x');
+    let file_backed = path.map(|p| p.starts_with('/')).unwrap_or(false);
+
+    Some(MapRegion {
+        start,
+        end,
+        readable,
+        exec_only_file_backed: file_backed && executable && !writable,
+    })
+}
+
+pub(super) fn scan_process_for_token(
+    pid: u32,
+    uuid_re: &Regex,
+    patterns: &(Vec<u8>, Vec<u8>),
+) -> Result<Option<String>> {
+    let handle: ProcessHandle = (pid as Pid).try_into().map_err(|e| anyhow!("打开进程用于读取失败: {e}"))?;
+
+    let maps_path = format!("/proc/{}/maps", pid);
+    let file = fs::File::open(&maps_path).map_err(|e| anyhow!("读取 {} 失败: {e}", maps_path))?;
+    let reader = BufReader::new(file);
+
+    let overlap = patterns.0.len().max(patterns.1.len()) + SCAN_AHEAD;
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+        let region = match parse_maps_line(&line) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        // guard 区域 (不可读) 以及仅可执行的文件映射段基本不会持有凭据，跳过以减少扫描量
+        if !region.readable || region.exec_only_file_backed {
+            continue;
+        }
+
+        let region_size = region.end.saturating_sub(region.start);
+        if region_size == 0 {
+            continue;
+        }
+        let capped = std::cmp::min(region_size, MAX_REGION_BYTES);
+
+        let mut offset = 0usize;
+        while offset < capped {
+            let chunk_size = std::cmp::min(CHUNK_SIZE, capped - offset);
+            let mut buffer = vec![0u8; chunk_size];
+            let read_res = handle
+                .copy_address(region.start + offset, &mut buffer)
+                .map(|_| chunk_size);
+
+            let read = match read_res {
+                Ok(n) => n,
+                Err(e) => {
+                    let step = std::cmp::max(1, chunk_size.saturating_sub(overlap));
+                    offset = offset.saturating_add(step);
+                    tracing::debug!(pid, base = region.start, offset, "process_vm_readv 失败: {e}");
+                    continue;
+                }
+            };
+
+            buffer.truncate(read);
+            if let Some(token) = search_bytes_for_token(&buffer, uuid_re, patterns) {
+                return Ok(Some(token));
+            }
+
+            let step = std::cmp::max(1, read.saturating_sub(overlap));
+            offset = offset.saturating_add(step);
+        }
+    }
+
+    Ok(None)
+}
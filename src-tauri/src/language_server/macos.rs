@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Result};
+use mach2::kern_return::KERN_SUCCESS;
+use mach2::port::mach_port_t;
+use mach2::traps::{mach_task_self, task_for_pid};
+use mach2::vm::mach_vm_read_overwrite;
+use mach2::vm_region::{vm_region_basic_info_data_64_t, VM_REGION_BASIC_INFO_64};
+use mach2::vm_types::{mach_vm_address_t, mach_vm_size_t};
+use regex::Regex;
+use std::mem::size_of;
+
+use crate::language_server::utils::{search_bytes_for_token, CHUNK_SIZE, SCAN_AHEAD, MAX_REGION_BYTES};
+
+const VM_PROT_READ: i32 = 0x01;
+
+extern "C" {
+    fn mach_vm_region(
+        target_task: mach_port_t,
+        address: *mut mach_vm_address_t,
+        size: *mut mach_vm_size_t,
+        flavor: i32,
+        info: *mut u8,
+        info_cnt: *mut u32,
+        object_name: *mut mach_port_t,
+    ) -> i32;
+}
+
+fn is_readable(protection: i32) -> bool {
+    protection & VM_PROT_READ != 0
+}
+
+pub(super) fn scan_process_for_token(
+    pid: u32,
+    uuid_re: &Regex,
+    patterns: &(Vec<u8>, Vec<u8>),
+) -> Result<Option<String>> {
+    let mut task: mach_port_t = 0;
+    let kr = unsafe { task_for_pid(mach_task_self(), pid as i32, &mut task) };
+    if kr != KERN_SUCCESS {
+        return Err(anyhow!("task_for_pid 失败 (需要辅助权限): {kr}"));
+    }
+
+    let overlap = patterns.0.len().max(patterns.1.len()) + SCAN_AHEAD;
+    let mut address: mach_vm_address_t = 0;
+
+    loop {
+        let mut size: mach_vm_size_t = 0;
+        let mut info: vm_region_basic_info_data_64_t = unsafe { std::mem::zeroed() };
+        let mut info_cnt = (size_of::<vm_region_basic_info_data_64_t>() / size_of::<u32>()) as u32;
+        let mut object_name: mach_port_t = 0;
+
+        let kr = unsafe {
+            mach_vm_region(
+                task,
+                &mut address,
+                &mut size,
+                VM_REGION_BASIC_INFO_64,
+                &mut info as *mut _ as *mut u8,
+                &mut info_cnt,
+                &mut object_name,
+            )
+        };
+        if kr != KERN_SUCCESS {
+            break; // 没有更多区域了
+        }
+
+        if size > 0 && is_readable(info.protection) {
+            let capped = std::cmp::min(size as usize, MAX_REGION_BYTES);
+            let mut offset = 0usize;
+
+            while offset < capped {
+                let chunk_size = std::cmp::min(CHUNK_SIZE, capped - offset);
+                let mut buffer = vec![0u8; chunk_size];
+                let mut read: mach_vm_size_t = 0;
+
+                let kr = unsafe {
+                    mach_vm_read_overwrite(
+                        task,
+                        address + offset as mach_vm_address_t,
+                        chunk_size as mach_vm_size_t,
+                        buffer.as_mut_ptr() as mach_vm_address_t,
+                        &mut read,
+                    )
+                };
+
+                if kr != KERN_SUCCESS {
+                    let step = std::cmp::max(1, chunk_size.saturating_sub(overlap));
+                    offset = offset.saturating_add(step);
+                    tracing::debug!(pid, base = address, offset, "mach_vm_read_overwrite 失败: {kr}");
+                    continue;
+                }
+
+                buffer.truncate(read as usize);
+                if let Some(token) = search_bytes_for_token(&buffer, uuid_re, patterns) {
+                    return Ok(Some(token));
+                }
+
+                let step = std::cmp::max(1, (read as usize).saturating_sub(overlap));
+                offset = offset.saturating_add(step);
+            }
+        }
+
+        let next = address.saturating_add(size);
+        if next <= address {
+            break;
+        }
+        address = next;
+    }
+
+    Ok(None)
+}
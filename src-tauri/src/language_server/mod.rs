@@ -0,0 +1,68 @@
+//! Antigravity 语言服务器账号状态接口
+//! `scan_process_for_token` 按平台选择具体实现，匹配逻辑 (`search_bytes_for_token`)
+//! 和扫描参数 (`CHUNK_SIZE` / `SCAN_AHEAD` / `MAX_REGION_BYTES`) 在各平台间完全复用，
+//! 只有枚举内存区域、读取内存的系统调用不同。
+
+mod utils;
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "windows")]
+use windows::scan_process_for_token;
+#[cfg(target_os = "linux")]
+use linux::scan_process_for_token;
+#[cfg(target_os = "macos")]
+use macos::scan_process_for_token;
+
+use regex::Regex;
+use serde::Serialize;
+use sysinfo::{ProcessExt, System, SystemExt};
+
+const LANGUAGE_SERVER_PROCESS_NAME: &str = "language_server";
+// access token 在语言服务器进程内存里以 `"accessToken":"<token>"` 这样的 JSON 片段出现
+const TOKEN_PREFIX: &[u8] = b"\"accessToken\":\"";
+const TOKEN_SUFFIX: &[u8] = b"\"";
+
+#[derive(Debug, Serialize)]
+pub struct UserStatusInfo {
+    pub logged_in: bool,
+    pub token: Option<String>,
+}
+
+fn find_language_server_pid() -> Option<u32> {
+    let mut system = System::new_all();
+    system.refresh_processes();
+    system
+        .processes()
+        .values()
+        .find(|p| p.name().to_lowercase().contains(LANGUAGE_SERVER_PROCESS_NAME))
+        .map(|p| p.pid().as_u32())
+}
+
+/// 查询当前登录状态：在语言服务器进程的内存里扫描 access token。
+/// 找不到语言服务器进程、或者扫描不到 token，都视为"未登录"而不是报错，
+/// 只有扫描本身失败（例如权限不足）才返回 Err。
+#[tauri::command]
+pub async fn language_server_get_user_status() -> Result<UserStatusInfo, String> {
+    let pid = match find_language_server_pid() {
+        Some(pid) => pid,
+        None => return Ok(UserStatusInfo { logged_in: false, token: None }),
+    };
+
+    let uuid_re = Regex::new(r"^[0-9a-zA-Z._-]{16,}$").map_err(|e| e.to_string())?;
+    let patterns = (TOKEN_PREFIX.to_vec(), TOKEN_SUFFIX.to_vec());
+
+    match scan_process_for_token(pid, &uuid_re, &patterns) {
+        Ok(Some(token)) => Ok(UserStatusInfo { logged_in: true, token: Some(token) }),
+        Ok(None) => Ok(UserStatusInfo { logged_in: false, token: None }),
+        Err(e) => {
+            tracing::warn!(target: "language_server::scan", error = %e, "扫描语言服务器进程内存失败");
+            Err(format!("扫描语言服务器进程失败: {}", e))
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! 跨平台共享的内存扫描参数与匹配逻辑
+//! 各平台后端（`windows` / `linux` / `macos`）只负责枚举内存区域和读取字节，
+//! 找 token 的匹配逻辑在这里统一实现，保证三端行为一致。
+
+use regex::Regex;
+
+/// 单次 `copy_address` 读取的字节数
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+/// 相邻 chunk 之间预留的重叠字节数，避免 token 正好跨在 chunk 边界上被漏掉
+pub const SCAN_AHEAD: usize = 64;
+/// 单个内存区域最多扫描的字节数，避免个别超大区域（如巨大的堆）拖慢整体扫描
+pub const MAX_REGION_BYTES: usize = 64 * 1024 * 1024;
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return None;
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// 在一块内存 buffer 里找 `patterns.0`（前缀）...`patterns.1`（后缀）之间夹着的文本，
+/// 并用 `uuid_re` 校验它看起来像一个 token（而不是误匹配的垃圾数据）。
+pub fn search_bytes_for_token(buffer: &[u8], uuid_re: &Regex, patterns: &(Vec<u8>, Vec<u8>)) -> Option<String> {
+    let (prefix, suffix) = patterns;
+    if prefix.is_empty() {
+        return None;
+    }
+
+    let mut scan_from = 0usize;
+    while let Some(rel_pos) = find_subslice(&buffer[scan_from..], prefix) {
+        let token_start = scan_from + rel_pos + prefix.len();
+        if token_start >= buffer.len() {
+            break;
+        }
+
+        // token 本身不会无限长，只在前缀之后的一小段窗口里找后缀
+        let window_end = std::cmp::min(buffer.len(), token_start + 512);
+        let window = &buffer[token_start..window_end];
+
+        if let Some(end_rel) = find_subslice(window, suffix) {
+            let token_bytes = &window[..end_rel];
+            if let Ok(token_str) = std::str::from_utf8(token_bytes) {
+                if uuid_re.is_match(token_str) {
+                    return Some(token_str.to_string());
+                }
+            }
+        }
+
+        scan_from = token_start;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns() -> (Vec<u8>, Vec<u8>) {
+        (b"\"accessToken\":\"".to_vec(), b"\"".to_vec())
+    }
+
+    fn uuid_re() -> Regex {
+        Regex::new(r"^[0-9a-zA-Z._-]{16,}$").unwrap()
+    }
+
+    #[test]
+    fn finds_token_between_prefix_and_suffix() {
+        let buffer = br#"{"accessToken":"abcdEFGH01234567","other":1}"#;
+        let found = search_bytes_for_token(buffer, &uuid_re(), &patterns());
+        assert_eq!(found.as_deref(), Some("abcdEFGH01234567"));
+    }
+
+    #[test]
+    fn finds_token_split_across_a_chunk_boundary_once_regions_overlap_by_scan_ahead() {
+        // 模拟两次 `copy_address` 读取的结果按 SCAN_AHEAD 重叠拼接后再整体匹配——
+        // 调用方负责重叠，这里验证只要重叠后的 buffer 完整包含了 token，匹配就不会因为
+        // token 原本横跨 chunk 边界而失败。
+        let full = br#"{"accessToken":"abcdEFGH01234567"}"#;
+        let split_at = full.len() - 10;
+        let mut first_chunk = full[..split_at].to_vec();
+        let overlap_start = split_at.saturating_sub(SCAN_AHEAD.min(split_at));
+        first_chunk.extend_from_slice(&full[overlap_start..]);
+
+        let found = search_bytes_for_token(&first_chunk, &uuid_re(), &patterns());
+        assert_eq!(found.as_deref(), Some("abcdEFGH01234567"));
+    }
+
+    #[test]
+    fn returns_none_when_prefix_is_missing() {
+        let buffer = b"no token prefix here";
+        assert_eq!(search_bytes_for_token(buffer, &uuid_re(), &patterns()), None);
+    }
+
+    #[test]
+    fn returns_none_when_prefix_has_no_closing_suffix() {
+        let buffer = br#"{"accessToken":"truncated-without-closing-quote"#;
+        assert_eq!(search_bytes_for_token(buffer, &uuid_re(), &patterns()), None);
+    }
+
+    #[test]
+    fn skips_a_match_that_fails_the_uuid_shape_check_and_keeps_scanning() {
+        let buffer = br#"{"accessToken":"short","accessToken":"abcdEFGH01234567"}"#;
+        let found = search_bytes_for_token(buffer, &uuid_re(), &patterns());
+        assert_eq!(found.as_deref(), Some("abcdEFGH01234567"));
+    }
+
+    #[test]
+    fn empty_prefix_never_matches() {
+        let buffer = b"anything";
+        let empty_patterns = (Vec::new(), b"\"".to_vec());
+        assert_eq!(search_bytes_for_token(buffer, &uuid_re(), &empty_patterns), None);
+    }
+}
@@ -19,12 +19,22 @@ mod commands;
 mod path_utils;
 mod state;
 mod setup;
+mod backup_archive;
+mod backup_retention;
+mod backup;
 
 // Re-export AppState for compatibility with other modules
 pub use state::{AppState, ProfileInfo, AntigravityAccount};
 
 // Use commands
 use crate::commands::*;
+use crate::antigravity::wal::recover_pending_operations;
+use crate::antigravity::repair::repair_antigravity_database;
+use crate::antigravity::backup::{backup_antigravity_current_account, restore_antigravity_account};
+use crate::backup::{backup_profile, restore_profile, restore_backup_files};
+use crate::backup_retention::garbage_collect_backups;
+use crate::utils::log_query::query_logs;
+use crate::language_server::language_server_get_user_status;
 
 fn main() {
     println!("🚀 启动 Antigravity Agent");
@@ -51,6 +61,7 @@ fn main() {
             restore_backup_files,
             delete_backup,
             clear_all_backups,
+            garbage_collect_backups,
             // Antigravity 相关命令
             switch_antigravity_account,
             get_antigravity_accounts,
@@ -59,6 +70,8 @@ fn main() {
             restore_antigravity_account,
             switch_to_antigravity_account,
             clear_all_antigravity_data,
+            recover_pending_operations,
+            repair_antigravity_database,
             // 进程管理命令
             kill_antigravity,
             is_antigravity_running,
@@ -95,6 +108,7 @@ fn main() {
             start_database_monitoring,
             stop_database_monitoring,
             get_log_info,
+            query_logs,
             clear_logs,
             decrypt_config_data,
             encrypt_config_data,
@@ -0,0 +1,23 @@
+//! 应用启动初始化
+//! 在主窗口创建之前做一次性的准备工作；目前这里只负责崩溃恢复扫描——
+//! 其余初始化职责（配置目录、托盘、数据库监控等）由各自的模块在启动路径的其它地方完成。
+
+use crate::antigravity::{repair, wal};
+
+pub fn init(_app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    // 扫描撤销日志，回滚上次清理操作中没有提交的修改（例如紧跟在 kill_antigravity 之后崩溃的场景）
+    match wal::recover_pending_operations() {
+        Ok(msg) => tracing::info!(target: "cleanup::wal", "{}", msg),
+        Err(e) => tracing::warn!(target: "cleanup::wal", error = %e, "启动时恢复撤销日志失败"),
+    }
+
+    // 启动时顺带做一次数据库完整性检查，损坏的数据库在用户遇到之前就尝试自动修复
+    tauri::async_runtime::spawn(async {
+        match repair::repair_antigravity_database().await {
+            Ok(report) => tracing::debug!(target: "cleanup::repair", ?report, "启动检查完成"),
+            Err(e) => tracing::debug!(target: "cleanup::repair", error = %e, "启动检查未执行（可能尚未安装 Antigravity）"),
+        }
+    });
+
+    Ok(())
+}
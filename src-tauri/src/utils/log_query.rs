@@ -0,0 +1,298 @@
+//! 对 `DailyLogFileAppender` 产出的 JSON 日志做结构化查询
+//!
+//! 每行日志都是一条脱敏后的 JSON 对象，带稳定的 `target`（`command::start`、
+//! `user_command::success`、`cleanup::database` 等）、`fields.duration_ms` 和时间戳。
+//! `get_log_info` 只返回粗粒度的文件元信息，这里补一个 `query_logs`，支持按日期范围、
+//! `target` 前缀、级别、自由文本子串过滤，并提供按命令统计次数 / 平均耗时 / 百分位的聚合。
+//! 按行流式解析、分页返回，避免一次性把多天日志都读进内存；即使遇到历史遗留的
+//! 未脱敏行，也统一走 `LogSanitizer` 处理，不会让敏感信息泄露出去。
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::log_sanitizer::LogSanitizer;
+
+/// 单条日志记录，字段对应 tracing 的 JSON 输出
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogRecord {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    #[serde(default)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LogRecord {
+    fn duration_ms(&self) -> Option<f64> {
+        self.fields.get("duration_ms").and_then(|v| v.as_f64())
+    }
+
+    fn message(&self) -> String {
+        self.fields
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// `query_logs` 的过滤条件
+#[derive(Debug, Default, Deserialize)]
+pub struct LogQuery {
+    /// 含起止的日期范围，格式 `YYYY-MM-DD`
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// 只保留 `target` 以此为前缀的记录，例如 `"cleanup::"`
+    pub target_prefix: Option<String>,
+    pub level: Option<String>,
+    /// 对 message 字段做子串匹配
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+}
+
+fn default_page_size() -> usize {
+    100
+}
+
+impl LogQuery {
+    fn matches(&self, record: &LogRecord) -> bool {
+        if let Some(prefix) = &self.target_prefix {
+            if !record.target.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(level) = &self.level {
+            if !record.level.eq_ignore_ascii_case(level) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            if !record.message().contains(needle.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 日志文件名形如 `antigravity-agent.2025-11-23.log`，用日期范围直接过滤文件
+    fn file_in_range(&self, file_date: &str) -> bool {
+        if let Some(start) = &self.start_date {
+            if file_date < start.as_str() {
+                return false;
+            }
+        }
+        if let Some(end) = &self.end_date {
+            if file_date > end.as_str() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 某个命令的聚合统计
+#[derive(Debug, Serialize)]
+pub struct CommandStats {
+    pub command: String,
+    pub count: usize,
+    pub avg_duration_ms: f64,
+    pub p50_duration_ms: f64,
+    pub p95_duration_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogQueryResult {
+    pub records: Vec<LogRecord>,
+    pub total_matched: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub stats: Vec<CommandStats>,
+}
+
+fn log_date_from_filename(file_prefix: &str, file_name: &str) -> Option<String> {
+    file_name
+        .strip_prefix(file_prefix)?
+        .strip_prefix('.')?
+        .strip_suffix(".log")
+        .map(|s| s.to_string())
+}
+
+fn list_log_files(log_dir: &Path, file_prefix: &str) -> std::io::Result<Vec<(String, std::path::PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(log_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n,
+            None => continue,
+        };
+        if let Some(date) = log_date_from_filename(file_prefix, name) {
+            files.push((date, path));
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// 流式扫描日志目录，按过滤条件收集匹配记录并分页，同时计算按 `command` 分组的统计
+fn scan_logs(log_dir: &Path, file_prefix: &str, query: LogQuery) -> Result<LogQueryResult, String> {
+    let sanitizer = LogSanitizer::new();
+    let files = list_log_files(log_dir, file_prefix).map_err(|e| format!("列出日志文件失败: {}", e))?;
+
+    let mut matched: Vec<LogRecord> = Vec::new();
+    let mut durations: std::collections::HashMap<String, Vec<f64>> = std::collections::HashMap::new();
+    let mut total_matched = 0usize;
+
+    let page_start = query.page.saturating_mul(query.page_size.max(1));
+    let page_end = page_start + query.page_size.max(1);
+
+    for (file_date, path) in files {
+        if !query.file_in_range(&file_date) {
+            continue;
+        }
+
+        let file = File::open(&path).map_err(|e| format!("打开日志文件失败: {}", e))?;
+        let reader = BufReader::new(file);
+
+        // 逐行解析，单行损坏不影响后续行，也不会把整份日志读进内存
+        for line in reader.lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // 即使遇到历史遗留的未脱敏行，也统一跑一遍脱敏，防止泄露
+            let sanitized = sanitizer.sanitize(&line);
+            let record: LogRecord = match serde_json::from_str(&sanitized) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            if !query.matches(&record) {
+                continue;
+            }
+
+            if let Some(command) = record.fields.get("command").and_then(|v| v.as_str()) {
+                if let Some(ms) = record.duration_ms() {
+                    durations.entry(command.to_string()).or_default().push(ms);
+                }
+            }
+
+            if total_matched >= page_start && total_matched < page_end {
+                matched.push(record);
+            }
+            total_matched += 1;
+        }
+    }
+
+    let mut stats: Vec<CommandStats> = durations
+        .into_iter()
+        .map(|(command, mut values)| {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            CommandStats {
+                command,
+                count: values.len(),
+                avg_duration_ms: average(&values),
+                p50_duration_ms: percentile(&values, 0.50),
+                p95_duration_ms: percentile(&values, 0.95),
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.count.cmp(&a.count));
+
+    Ok(LogQueryResult {
+        records: matched,
+        total_matched,
+        page: query.page,
+        page_size: query.page_size,
+        stats,
+    })
+}
+
+/// 日志文件固定写在 `<config_dir>/logs`，文件名前缀固定为 `antigravity-agent`，
+/// 与 `tracing_config::init_tracing` 保持一致，调用方不需要关心具体路径
+const LOG_FILE_PREFIX: &str = "antigravity-agent";
+
+/// 对外暴露的 `query_logs` 命令：只接收过滤条件，日志目录由服务端自行解析，
+/// 这样前端只需要传 serde 友好的 [`LogQuery`]，不需要知道磁盘布局。
+#[tauri::command]
+pub async fn query_logs(query: LogQuery) -> Result<LogQueryResult, String> {
+    let log_dir = crate::path_utils::config_dir().join("logs");
+    scan_logs(&log_dir, LOG_FILE_PREFIX, query)
+}
+
+fn average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn percentile(sorted_values: &[f64], p: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted_values.len() as f64 - 1.0) * p).round() as usize;
+    sorted_values[idx.min(sorted_values.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_of_empty_input_is_zero() {
+        assert_eq!(average(&[]), 0.0);
+    }
+
+    #[test]
+    fn average_is_arithmetic_mean() {
+        assert_eq!(average(&[1.0, 2.0, 3.0]), 2.0);
+    }
+
+    #[test]
+    fn percentile_of_empty_input_is_zero() {
+        assert_eq!(percentile(&[], 0.95), 0.0);
+    }
+
+    #[test]
+    fn percentile_p50_of_odd_length_is_the_middle_value() {
+        assert_eq!(percentile(&[10.0, 20.0, 30.0], 0.50), 20.0);
+    }
+
+    #[test]
+    fn percentile_p95_rounds_to_nearest_rank() {
+        let values: Vec<f64> = (1..=20).map(|n| n as f64).collect();
+        // (20 - 1) * 0.95 = 18.05 -> rounds to index 18 -> 19th value (1-indexed)
+        assert_eq!(percentile(&values, 0.95), 19.0);
+    }
+
+    #[test]
+    fn log_query_matches_filters_on_target_prefix_level_and_contains() {
+        let record = LogRecord {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            level: "INFO".to_string(),
+            target: "cleanup::wal".to_string(),
+            fields: serde_json::json!({"message": "撤销日志已提交"}).as_object().unwrap().clone(),
+        };
+
+        let query = LogQuery {
+            target_prefix: Some("cleanup::".to_string()),
+            level: Some("info".to_string()),
+            contains: Some("撤销日志".to_string()),
+            ..LogQuery::default()
+        };
+        assert!(query.matches(&record));
+
+        let mismatched = LogQuery { target_prefix: Some("backup::".to_string()), ..LogQuery::default() };
+        assert!(!mismatched.matches(&record));
+    }
+}
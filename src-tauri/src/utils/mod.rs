@@ -0,0 +1,7 @@
+//! 通用工具模块：日志宏、tracing 初始化、脱敏、结构化日志查询
+
+#[macro_use]
+pub mod log_decorator;
+pub mod log_sanitizer;
+pub mod tracing_config;
+pub mod log_query;